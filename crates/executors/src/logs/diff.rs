@@ -0,0 +1,315 @@
+//! Parsing of unified diffs into structured hunks, with reconstruction of
+//! hunk-header line numbers for diffs whose headers aren't trustworthy.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// One line within a [`Hunk`], tagged by its diff role.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+#[ts(export)]
+pub enum DiffLine {
+    Context(String),
+    Added(String),
+    Removed(String),
+}
+
+/// One `@@ -old_start,old_count +new_start,new_count @@` hunk of a unified
+/// diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_count: u32,
+    pub new_start: u32,
+    pub new_count: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+/// A parsed unified diff for a single file.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ParsedDiff {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Errors parsing a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffParseError {
+    MalformedHunkHeader(String),
+    AnchorNotFound,
+}
+
+impl std::fmt::Display for DiffParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffParseError::MalformedHunkHeader(header) => {
+                write!(f, "malformed hunk header: {header}")
+            }
+            DiffParseError::AnchorNotFound => {
+                write!(f, "could not locate hunk's anchor context line in original source")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DiffParseError {}
+
+/// Parses `diff` into a [`ParsedDiff`]. When `has_line_numbers` is false the
+/// `@@` header's line numbers are untrusted and recomputed by locating each
+/// hunk's first context line inside `original_source`, then walking forward:
+/// context lines advance both old/new counters, removed lines advance only
+/// the old counter, added lines advance only the new counter. Tolerates
+/// multiple hunks per file and trailing `\ No newline at end of file`
+/// markers.
+pub fn parse_unified_diff(
+    diff: &str,
+    has_line_numbers: bool,
+    original_source: Option<&str>,
+) -> Result<ParsedDiff, DiffParseError> {
+    let source_lines: Vec<&str> = original_source.map(|s| s.lines().collect()).unwrap_or_default();
+
+    let mut old_path = None;
+    let mut new_path = None;
+    let mut hunks = Vec::new();
+    let mut line_delta: i64 = 0;
+    // Tracks where the *next* hunk is expected to start in the old file,
+    // so reconstruction can pick the anchor occurrence nearest that offset
+    // instead of blindly matching the first occurrence in the whole file.
+    let mut expected_old_start: u32 = 1;
+
+    let mut lines = diff.lines().peekable();
+    while let Some(line) = lines.next() {
+        if let Some(path) = line.strip_prefix("--- ") {
+            old_path = Some(strip_diff_path(path));
+            continue;
+        }
+        if let Some(path) = line.strip_prefix("+++ ") {
+            new_path = Some(strip_diff_path(path));
+            continue;
+        }
+        if !line.starts_with("@@") {
+            continue;
+        }
+
+        let header = parse_hunk_header(line)
+            .ok_or_else(|| DiffParseError::MalformedHunkHeader(line.to_string()))?;
+
+        let mut hunk_lines = Vec::new();
+        while let Some(&next) = lines.peek() {
+            if next.starts_with("@@") || next.starts_with("--- ") || next.starts_with("+++ ") {
+                break;
+            }
+            let body = lines.next().unwrap();
+            if body == "\\ No newline at end of file" {
+                continue;
+            }
+            hunk_lines.push(match body.as_bytes().first() {
+                Some(b'+') => DiffLine::Added(body[1..].to_string()),
+                Some(b'-') => DiffLine::Removed(body[1..].to_string()),
+                Some(b' ') => DiffLine::Context(body[1..].to_string()),
+                _ => DiffLine::Context(body.to_string()),
+            });
+        }
+
+        let hunk = if has_line_numbers {
+            let (old_start, old_count, new_start, new_count) = header;
+            Hunk {
+                old_start,
+                old_count,
+                new_start,
+                new_count,
+                lines: hunk_lines,
+            }
+        } else {
+            reconstruct_hunk(hunk_lines, &source_lines, expected_old_start, line_delta)?
+        };
+
+        expected_old_start = hunk.old_start + hunk.old_count;
+        line_delta += hunk.new_count as i64 - hunk.old_count as i64;
+        hunks.push(hunk);
+    }
+
+    Ok(ParsedDiff {
+        old_path,
+        new_path,
+        hunks,
+    })
+}
+
+fn strip_diff_path(path: &str) -> String {
+    path.split('\t').next().unwrap_or(path).to_string()
+}
+
+fn parse_hunk_header(line: &str) -> Option<(u32, u32, u32, u32)> {
+    let body = line.trim_start_matches('@').trim_end_matches('@').trim();
+    let mut parts = body.split_whitespace();
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let (old_start, old_count) = parse_range(old)?;
+    let (new_start, new_count) = parse_range(new)?;
+    Some((old_start, old_count, new_start, new_count))
+}
+
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    let mut parts = range.splitn(2, ',');
+    let start: u32 = parts.next()?.parse().ok()?;
+    let count: u32 = match parts.next() {
+        Some(count) => count.parse().ok()?,
+        None => 1,
+    };
+    Some((start, count))
+}
+
+/// Recomputes a hunk's line numbers by locating its first context line
+/// inside `source_lines`, then counting old/new lines before and after that
+/// anchor. Anchor text like `}` or a blank line can occur many times in a
+/// file, so matches are disambiguated by picking the occurrence closest to
+/// `expected_old_start` (where the previous hunk left off, or `1` for the
+/// first hunk) rather than the first occurrence anywhere. `line_delta` is
+/// the running `new_count - old_count` total of every preceding hunk in the
+/// same diff, used to place `new_start` without needing the post-patch file.
+fn reconstruct_hunk(
+    lines: Vec<DiffLine>,
+    source_lines: &[&str],
+    expected_old_start: u32,
+    line_delta: i64,
+) -> Result<Hunk, DiffParseError> {
+    let anchor_index_in_hunk = lines
+        .iter()
+        .position(|line| matches!(line, DiffLine::Context(_)));
+
+    let old_start = match anchor_index_in_hunk {
+        Some(anchor_index_in_hunk) => {
+            let DiffLine::Context(anchor_text) = &lines[anchor_index_in_hunk] else {
+                unreachable!("anchor_index_in_hunk points at a Context line")
+            };
+            let old_lines_before_anchor = lines[..anchor_index_in_hunk]
+                .iter()
+                .filter(|line| !matches!(line, DiffLine::Added(_)))
+                .count() as u32;
+            let expected_anchor_index =
+                expected_old_start.saturating_sub(1) + old_lines_before_anchor;
+
+            let source_index = source_lines
+                .iter()
+                .enumerate()
+                .filter(|&(_, line)| *line == anchor_text)
+                .min_by_key(|(index, _)| (*index as i64 - expected_anchor_index as i64).abs())
+                .map(|(index, _)| index as u32)
+                .ok_or(DiffParseError::AnchorNotFound)?;
+
+            source_index.saturating_sub(old_lines_before_anchor) + 1
+        }
+        None => expected_old_start.max(1),
+    };
+
+    let mut old_count = 0u32;
+    let mut new_count = 0u32;
+    for line in &lines {
+        match line {
+            DiffLine::Context(_) => {
+                old_count += 1;
+                new_count += 1;
+            }
+            DiffLine::Removed(_) => old_count += 1,
+            DiffLine::Added(_) => new_count += 1,
+        }
+    }
+
+    let new_start = (old_start as i64 + line_delta).max(1) as u32;
+
+    Ok(Hunk {
+        old_start,
+        old_count,
+        new_start,
+        new_count,
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trusts_the_header_when_line_numbers_are_reliable() {
+        let diff = "--- a/file.rs\n+++ b/file.rs\n@@ -10,2 +10,2 @@\n-old\n+new\n";
+        let parsed = parse_unified_diff(diff, true, None).unwrap();
+
+        assert_eq!(parsed.old_path.as_deref(), Some("a/file.rs"));
+        assert_eq!(parsed.new_path.as_deref(), Some("b/file.rs"));
+        assert_eq!(parsed.hunks.len(), 1);
+        assert_eq!(parsed.hunks[0].old_start, 10);
+        assert_eq!(parsed.hunks[0].new_start, 10);
+    }
+
+    #[test]
+    fn parses_multiple_hunks_in_one_diff() {
+        let diff = "@@ -1,1 +1,1 @@\n-a\n+b\n@@ -5,1 +5,1 @@\n-c\n+d\n";
+        let parsed = parse_unified_diff(diff, true, None).unwrap();
+        assert_eq!(parsed.hunks.len(), 2);
+        assert_eq!(parsed.hunks[0].old_start, 1);
+        assert_eq!(parsed.hunks[1].old_start, 5);
+    }
+
+    #[test]
+    fn tolerates_no_newline_at_end_of_file_marker() {
+        let diff = "@@ -1,1 +1,1 @@\n-old\n+new\n\\ No newline at end of file\n";
+        let parsed = parse_unified_diff(diff, true, None).unwrap();
+        assert_eq!(parsed.hunks[0].lines.len(), 2);
+    }
+
+    #[test]
+    fn reconstructs_old_start_from_a_unique_anchor() {
+        let source = "fn foo() {\n    x = 1;\n}\n";
+        // The header claims line 99, but `has_line_numbers: false` means it
+        // must be ignored in favor of locating "fn foo() {" in `source`.
+        let diff = "@@ -99,3 +99,3 @@\n fn foo() {\n-    x = 1;\n+    x = 2;\n }\n";
+        let parsed = parse_unified_diff(diff, false, Some(source)).unwrap();
+
+        assert_eq!(parsed.hunks[0].old_start, 1);
+        assert_eq!(parsed.hunks[0].new_start, 1);
+        assert_eq!(parsed.hunks[0].old_count, 3);
+        assert_eq!(parsed.hunks[0].new_count, 3);
+    }
+
+    #[test]
+    fn disambiguates_a_repeated_anchor_using_the_expected_offset() {
+        // "}" closes three different blocks, so a naive "first match
+        // anywhere in the file" search would anchor hunk B to line 1
+        // instead of its real location at line 4.
+        let source = "}\nfn foo() {\n    x = 1;\n}\nfn bar() {\n    x = 1;\n}\n";
+        let diff = concat!(
+            "@@ -1,3 +1,3 @@\n",
+            " fn foo() {\n",
+            "-    x = 1;\n",
+            "+    x = 2;\n",
+            " }\n",
+            "@@ -1,4 +1,4 @@\n",
+            " }\n",
+            " fn bar() {\n",
+            "-    x = 1;\n",
+            "+    x = 2;\n",
+            " }\n",
+        );
+
+        let parsed = parse_unified_diff(diff, false, Some(source)).unwrap();
+        assert_eq!(parsed.hunks.len(), 2);
+        assert_eq!(parsed.hunks[0].old_start, 2, "foo's body starts at line 2");
+        assert_eq!(
+            parsed.hunks[1].old_start, 4,
+            "the second hunk's leading '}}' is foo's closing brace (line 4), not line 1"
+        );
+    }
+
+    #[test]
+    fn missing_anchor_is_an_error_not_a_panic() {
+        let source = "something else entirely\n";
+        let diff = "@@ -1,1 +1,1 @@\n this line is not in source\n-old\n+new\n";
+        let result = parse_unified_diff(diff, false, Some(source));
+        assert_eq!(result, Err(DiffParseError::AnchorNotFound));
+    }
+}
@@ -1,9 +1,18 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+// Leading `::` disambiguates from this module's own `utils` submodule below.
+use ::utils::shell;
 
+pub mod diff;
+pub mod normalizer;
+pub mod outcome;
+pub mod pipeline;
 pub mod plain_text_processor;
 pub mod stderr_processor;
 pub mod utils;
+pub mod workspace_edit;
+
+pub use outcome::CommandOutcome;
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -34,6 +43,22 @@ pub enum CommandExitStatus {
 pub struct CommandRunResult {
     pub exit_status: Option<CommandExitStatus>,
     pub output: Option<String>,
+    /// Derived classification of `exit_status`/`output`, letting a caller
+    /// distinguish a broken build from a passing build whose tests failed.
+    #[serde(default)]
+    pub outcome: CommandOutcome,
+}
+
+impl CommandRunResult {
+    /// Builds a result, computing `outcome` via [`outcome::classify`].
+    pub fn new(exit_status: Option<CommandExitStatus>, output: Option<String>) -> Self {
+        let outcome = outcome::classify(exit_status.as_ref(), output.as_deref());
+        Self {
+            exit_status,
+            output,
+            outcome,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -93,6 +118,12 @@ pub enum ActionType {
         command: String,
         #[serde(default)]
         result: Option<CommandRunResult>,
+        /// `command` parsed via `utils::shell::parse`, so a UI can render
+        /// each stage of a pipeline/sequence instead of the raw string.
+        /// `None` when `command` couldn't be parsed, e.g. unsupported shell
+        /// syntax.
+        #[serde(default)]
+        parsed: Option<shell::Command>,
     },
     Search {
         query: String,
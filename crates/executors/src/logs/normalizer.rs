@@ -0,0 +1,239 @@
+//! Pluggable dispatch from raw agent output to [`NormalizedEntry`] values.
+//!
+//! `NormalizedConversation::executor_type` is a bare string, and historically
+//! the logic that turns raw output into entries lived in fixed modules
+//! (`plain_text_processor`, `stderr_processor`) selected by matching on that
+//! string. [`LogNormalizer`] replaces the implicit matching with an
+//! extensible, testable dispatch surface so a downstream crate can register
+//! support for a new coding agent's log format without editing core code.
+
+use super::{plain_text_processor, stderr_processor, NormalizedEntry};
+
+/// One chunk of an agent's raw output, tagged with the executor that
+/// produced it.
+#[derive(Debug, Clone, Copy)]
+pub struct RawChunk<'a> {
+    pub executor_type: &'a str,
+    pub content: &'a str,
+}
+
+/// Errors produced while normalizing a [`RawChunk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NormalizeError {
+    /// No registered [`LogNormalizer`] claims this executor type.
+    Unsupported(String),
+    /// A normalizer recognized the executor type but couldn't parse the chunk.
+    Parse(String),
+}
+
+impl std::fmt::Display for NormalizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NormalizeError::Unsupported(executor_type) => {
+                write!(f, "no log normalizer registered for executor type: {executor_type}")
+            }
+            NormalizeError::Parse(reason) => write!(f, "failed to normalize log chunk: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for NormalizeError {}
+
+/// Turns an executor's raw log output into [`NormalizedEntry`] values.
+/// Implementors are registered with a [`LogNormalizerRegistry`] and
+/// dispatched on [`LogNormalizer::executor_type`], mirroring how other
+/// backend traits in this codebase let new implementations be plugged in
+/// without editing core code.
+pub trait LogNormalizer: Send + Sync {
+    /// The `executor_type` string this normalizer handles.
+    fn executor_type(&self) -> &str;
+
+    /// Normalizes one raw chunk into entries.
+    fn normalize(&self, raw: &RawChunk) -> Result<Vec<NormalizedEntry>, NormalizeError>;
+}
+
+/// Built-in [`LogNormalizer`] wrapping [`plain_text_processor`].
+pub struct PlainTextNormalizer;
+
+impl LogNormalizer for PlainTextNormalizer {
+    fn executor_type(&self) -> &str {
+        "plain_text"
+    }
+
+    fn normalize(&self, raw: &RawChunk) -> Result<Vec<NormalizedEntry>, NormalizeError> {
+        Ok(plain_text_processor::normalize(raw.content))
+    }
+}
+
+/// Built-in [`LogNormalizer`] wrapping [`stderr_processor`].
+pub struct StderrNormalizer;
+
+impl LogNormalizer for StderrNormalizer {
+    fn executor_type(&self) -> &str {
+        "stderr"
+    }
+
+    fn normalize(&self, raw: &RawChunk) -> Result<Vec<NormalizedEntry>, NormalizeError> {
+        Ok(stderr_processor::normalize(raw.content))
+    }
+}
+
+/// A registry of [`LogNormalizer`]s, dispatching on `executor_type`.
+///
+/// Ships with the plain-text and stderr processors pre-registered; call
+/// [`LogNormalizerRegistry::register`] to add support for another agent's
+/// log format.
+pub struct LogNormalizerRegistry {
+    normalizers: Vec<Box<dyn LogNormalizer>>,
+}
+
+impl Default for LogNormalizerRegistry {
+    fn default() -> Self {
+        Self {
+            normalizers: vec![Box::new(PlainTextNormalizer), Box::new(StderrNormalizer)],
+        }
+    }
+}
+
+impl LogNormalizerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `normalizer`, taking precedence over any existing
+    /// normalizer for the same `executor_type`.
+    pub fn register(&mut self, normalizer: Box<dyn LogNormalizer>) {
+        self.normalizers
+            .retain(|existing| existing.executor_type() != normalizer.executor_type());
+        self.normalizers.push(normalizer);
+    }
+
+    /// Normalizes `raw` using whichever registered normalizer claims its
+    /// `executor_type`.
+    pub fn normalize(&self, raw: &RawChunk) -> Result<Vec<NormalizedEntry>, NormalizeError> {
+        self.normalizers
+            .iter()
+            .find(|normalizer| normalizer.executor_type() == raw.executor_type)
+            .ok_or_else(|| NormalizeError::Unsupported(raw.executor_type.to_string()))?
+            .normalize(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A dummy [`LogNormalizer`] that doesn't depend on any of the real
+    /// built-in processors, so these tests stay focused on registry
+    /// dispatch rather than log-format parsing.
+    struct DummyNormalizer {
+        executor_type: &'static str,
+        entry_content: &'static str,
+    }
+
+    impl LogNormalizer for DummyNormalizer {
+        fn executor_type(&self) -> &str {
+            self.executor_type
+        }
+
+        fn normalize(&self, raw: &RawChunk) -> Result<Vec<NormalizedEntry>, NormalizeError> {
+            if raw.content.is_empty() {
+                return Err(NormalizeError::Parse("empty chunk".to_string()));
+            }
+            Ok(vec![NormalizedEntry {
+                timestamp: None,
+                entry_type: super::super::NormalizedEntryType::AssistantMessage,
+                content: self.entry_content.to_string(),
+                metadata: None,
+            }])
+        }
+    }
+
+    #[test]
+    fn dispatches_to_the_normalizer_registered_for_the_executor_type() {
+        let mut registry = LogNormalizerRegistry::new();
+        registry.register(Box::new(DummyNormalizer {
+            executor_type: "dummy",
+            entry_content: "from dummy",
+        }));
+
+        let entries = registry
+            .normalize(&RawChunk {
+                executor_type: "dummy",
+                content: "some output",
+            })
+            .unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "from dummy");
+    }
+
+    #[test]
+    fn unregistered_executor_type_is_unsupported() {
+        let registry = LogNormalizerRegistry::new();
+
+        let err = registry
+            .normalize(&RawChunk {
+                executor_type: "some_unknown_agent",
+                content: "anything",
+            })
+            .unwrap_err();
+
+        assert_eq!(err, NormalizeError::Unsupported("some_unknown_agent".to_string()));
+    }
+
+    #[test]
+    fn registering_a_normalizer_for_an_existing_executor_type_overrides_it() {
+        let mut registry = LogNormalizerRegistry::new();
+        registry.register(Box::new(DummyNormalizer {
+            executor_type: "plain_text",
+            entry_content: "overridden",
+        }));
+
+        let entries = registry
+            .normalize(&RawChunk {
+                executor_type: "plain_text",
+                content: "some output",
+            })
+            .unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "overridden");
+    }
+
+    #[test]
+    fn a_normalizer_error_propagates_through_the_registry() {
+        let mut registry = LogNormalizerRegistry::new();
+        registry.register(Box::new(DummyNormalizer {
+            executor_type: "dummy",
+            entry_content: "unused",
+        }));
+
+        let err = registry
+            .normalize(&RawChunk {
+                executor_type: "dummy",
+                content: "",
+            })
+            .unwrap_err();
+
+        assert_eq!(err, NormalizeError::Parse("empty chunk".to_string()));
+    }
+
+    #[test]
+    fn default_registry_has_the_built_in_plain_text_and_stderr_normalizers() {
+        let registry = LogNormalizerRegistry::default();
+
+        assert!(registry
+            .normalize(&RawChunk {
+                executor_type: "plain_text",
+                content: "hello",
+            })
+            .is_ok());
+        assert!(registry
+            .normalize(&RawChunk {
+                executor_type: "stderr",
+                content: "hello",
+            })
+            .is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+//! Semantic classification of a [`CommandRunResult`](super::CommandRunResult)
+//! beyond its raw exit code, so a UI can distinguish a broken build from a
+//! passing build whose tests failed.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::CommandExitStatus;
+
+/// A derived, tri-state classification of a command's result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum CommandOutcome {
+    BuildFail,
+    TestFail,
+    TestPass,
+}
+
+impl Default for CommandOutcome {
+    /// Ambiguous signals default to `BuildFail`, since silently treating a
+    /// broken build as a mere test failure is the more dangerous mistake.
+    fn default() -> Self {
+        CommandOutcome::BuildFail
+    }
+}
+
+// Markers that must anchor the *start* of a (trimmed) line, since they're
+// summary lines a test runner prints, not substrings safe to find anywhere
+// in arbitrary output (a bare "passed" or "Tests:" would also match words
+// like "bypassed" or unrelated prose).
+const TEST_FAIL_LINE_MARKERS: &[&str] = &["test result: FAILED", "FAILED", "failures:"];
+const TEST_PASS_LINE_MARKERS: &[&str] = &["test result: ok", "all tests passed"];
+
+// These are distinctive enough (compiler diagnostic prefixes, an exception
+// class name) to check as plain substrings.
+const TEST_FAIL_SUBSTRING_MARKERS: &[&str] = &["AssertionError"];
+const COMPILER_ERROR_MARKERS: &[&str] = &[
+    "error[E",
+    "error:",
+    "cannot find",
+    "compilation failed",
+    "SyntaxError",
+];
+
+/// Classifies a command's outcome from its exit status plus lightweight
+/// heuristics over its captured `output` (detecting test-runner summary
+/// lines vs compiler error signatures). Defaults to [`CommandOutcome::BuildFail`]
+/// whenever the signal is ambiguous.
+pub fn classify(exit_status: Option<&CommandExitStatus>, output: Option<&str>) -> CommandOutcome {
+    let succeeded = match exit_status {
+        Some(CommandExitStatus::ExitCode { code }) => *code == 0,
+        Some(CommandExitStatus::Success { success }) => *success,
+        None => return CommandOutcome::default(),
+    };
+
+    let output = output.unwrap_or_default();
+
+    if succeeded {
+        return CommandOutcome::TestPass;
+    }
+
+    if any_line_starts_with(output, TEST_FAIL_LINE_MARKERS)
+        || TEST_FAIL_SUBSTRING_MARKERS.iter().any(|marker| output.contains(marker))
+    {
+        return CommandOutcome::TestFail;
+    }
+
+    if any_line_starts_with(output, TEST_PASS_LINE_MARKERS)
+        && !COMPILER_ERROR_MARKERS.iter().any(|marker| output.contains(marker))
+    {
+        // The suite ran and reported success, but the command still exited
+        // non-zero (e.g. a post-test lint step failing) — treat as a test
+        // failure rather than a build failure.
+        return CommandOutcome::TestFail;
+    }
+
+    CommandOutcome::default()
+}
+
+/// Whether any (trimmed) line of `output` starts with one of `markers`.
+fn any_line_starts_with(output: &str, markers: &[&str]) -> bool {
+    output
+        .lines()
+        .any(|line| markers.iter().any(|marker| line.trim_start().starts_with(marker)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_exit_status_defaults_to_build_fail() {
+        assert_eq!(classify(None, None), CommandOutcome::BuildFail);
+    }
+
+    #[test]
+    fn successful_exit_is_always_test_pass() {
+        assert_eq!(
+            classify(Some(&CommandExitStatus::ExitCode { code: 0 }), Some("anything")),
+            CommandOutcome::TestPass
+        );
+        assert_eq!(
+            classify(Some(&CommandExitStatus::Success { success: true }), None),
+            CommandOutcome::TestPass
+        );
+    }
+
+    #[test]
+    fn failing_exit_with_no_output_is_build_fail() {
+        assert_eq!(
+            classify(Some(&CommandExitStatus::ExitCode { code: 1 }), None),
+            CommandOutcome::BuildFail
+        );
+    }
+
+    #[test]
+    fn test_runner_failure_summary_is_test_fail() {
+        let output = "running 3 tests\ntest result: FAILED. 2 passed; 1 failed\n";
+        assert_eq!(
+            classify(Some(&CommandExitStatus::ExitCode { code: 1 }), Some(output)),
+            CommandOutcome::TestFail
+        );
+    }
+
+    #[test]
+    fn post_test_step_failing_after_a_passing_suite_is_test_fail_not_build_fail() {
+        let output = "test result: ok. 4 passed; 0 failed\n";
+        assert_eq!(
+            classify(Some(&CommandExitStatus::ExitCode { code: 1 }), Some(output)),
+            CommandOutcome::TestFail
+        );
+    }
+
+    #[test]
+    fn compiler_error_after_a_reported_pass_is_not_test_fail() {
+        // A build step prints a stale "test result: ok" from a cached run,
+        // then a later compile step fails — the compiler error marker must
+        // win over the pass marker so this isn't misreported as TestFail.
+        let output = "test result: ok. 4 passed; 0 failed\nerror: could not compile `crate`\n";
+        assert_eq!(
+            classify(Some(&CommandExitStatus::ExitCode { code: 1 }), Some(output)),
+            CommandOutcome::BuildFail
+        );
+    }
+
+    // Regression tests for the line-anchored marker fix in 999bb56: generic
+    // substrings like "passed" or "Tests:" used to match arbitrary prose,
+    // misclassifying a build failure as a test outcome.
+    #[test]
+    fn bare_substring_matches_in_prose_do_not_trigger_test_fail() {
+        let output = "note: this step was bypassed due to a missing dependency\n";
+        assert_eq!(
+            classify(Some(&CommandExitStatus::ExitCode { code: 1 }), Some(output)),
+            CommandOutcome::BuildFail
+        );
+    }
+
+    #[test]
+    fn bare_substring_matches_in_prose_do_not_trigger_test_pass() {
+        let output = "Tests: see the README for how this crate is organized\n";
+        assert_eq!(
+            classify(Some(&CommandExitStatus::ExitCode { code: 1 }), Some(output)),
+            CommandOutcome::BuildFail
+        );
+    }
+
+    #[test]
+    fn markers_only_match_at_the_start_of_a_trimmed_line() {
+        assert!(any_line_starts_with(
+            "  test result: FAILED. 0 passed; 1 failed\n",
+            TEST_FAIL_LINE_MARKERS
+        ));
+        assert!(!any_line_starts_with(
+            "this line was not bypassed\n",
+            TEST_FAIL_LINE_MARKERS
+        ));
+    }
+}
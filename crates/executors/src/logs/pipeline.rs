@@ -0,0 +1,198 @@
+//! Coalescing of consecutive `CommandRun` entries in a
+//! [`NormalizedConversation`] into named [`Pipeline`]s, so a front-end can
+//! collapse a long setup sequence into a single expandable block showing
+//! which step failed and which never ran.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::{
+    ActionType, CommandOutcome, CommandRunResult, NormalizedConversation, NormalizedEntry,
+    NormalizedEntryType,
+};
+
+/// One step of a coalesced [`Pipeline`]: the command that ran, plus its
+/// result if it actually executed.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Step {
+    pub command: String,
+    pub result: Option<CommandRunResult>,
+    /// `true` once an earlier step in the same pipeline failed, meaning this
+    /// step never ran.
+    pub skipped: bool,
+}
+
+/// A run of consecutive setup/command entries, collapsed into one logical
+/// unit with short-circuit semantics.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Pipeline {
+    pub name: Option<String>,
+    pub steps: Vec<Step>,
+}
+
+/// Walks `conversation`'s entries and coalesces consecutive
+/// `ActionType::CommandRun` tool-use entries into [`Pipeline`]s. Once a
+/// step's outcome is non-success, every later step in that pipeline is
+/// marked `skipped` rather than run. `Pipeline::name` is left `None` here;
+/// callers that can derive a label (e.g. from a preceding user message)
+/// should set it afterwards.
+pub fn group_pipelines(conversation: &NormalizedConversation) -> Vec<Pipeline> {
+    let mut pipelines = Vec::new();
+    let mut current: Option<Pipeline> = None;
+    let mut short_circuited = false;
+
+    for entry in &conversation.entries {
+        match command_run(entry) {
+            Some((command, result)) => {
+                if current.is_none() {
+                    short_circuited = false;
+                }
+                let pipeline = current.get_or_insert_with(|| Pipeline {
+                    name: None,
+                    steps: Vec::new(),
+                });
+
+                let skipped = short_circuited;
+                if !skipped && result.is_some_and(|result| result.outcome != CommandOutcome::TestPass) {
+                    short_circuited = true;
+                }
+
+                pipeline.steps.push(Step {
+                    command: command.to_string(),
+                    result: result.cloned(),
+                    skipped,
+                });
+            }
+            None => {
+                if let Some(pipeline) = current.take() {
+                    pipelines.push(pipeline);
+                }
+            }
+        }
+    }
+
+    if let Some(pipeline) = current.take() {
+        pipelines.push(pipeline);
+    }
+
+    pipelines
+}
+
+fn command_run(entry: &NormalizedEntry) -> Option<(&str, Option<&CommandRunResult>)> {
+    match &entry.entry_type {
+        NormalizedEntryType::ToolUse {
+            action_type: ActionType::CommandRun { command, result, .. },
+            ..
+        } => Some((command.as_str(), result.as_ref())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::CommandExitStatus;
+
+    fn command_entry(command: &str, exit_code: Option<i32>) -> NormalizedEntry {
+        let result = exit_code.map(|code| {
+            CommandRunResult::new(Some(CommandExitStatus::ExitCode { code }), None)
+        });
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "command_run".to_string(),
+                action_type: ActionType::CommandRun {
+                    command: command.to_string(),
+                    result,
+                    parsed: None,
+                },
+            },
+            content: command.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn other_entry() -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: "not a command".to_string(),
+            metadata: None,
+        }
+    }
+
+    fn conversation(entries: Vec<NormalizedEntry>) -> NormalizedConversation {
+        NormalizedConversation {
+            entries,
+            session_id: None,
+            executor_type: "test".to_string(),
+            prompt: None,
+            summary: None,
+        }
+    }
+
+    #[test]
+    fn consecutive_command_runs_coalesce_into_one_pipeline() {
+        let pipelines = group_pipelines(&conversation(vec![
+            command_entry("cargo build", Some(0)),
+            command_entry("cargo test", Some(0)),
+        ]));
+
+        assert_eq!(pipelines.len(), 1);
+        assert_eq!(pipelines[0].steps.len(), 2);
+        assert!(pipelines[0].steps.iter().all(|step| !step.skipped));
+    }
+
+    #[test]
+    fn a_non_command_entry_splits_pipelines() {
+        let pipelines = group_pipelines(&conversation(vec![
+            command_entry("cargo build", Some(0)),
+            other_entry(),
+            command_entry("cargo test", Some(0)),
+        ]));
+
+        assert_eq!(pipelines.len(), 2);
+        assert_eq!(pipelines[0].steps.len(), 1);
+        assert_eq!(pipelines[1].steps.len(), 1);
+    }
+
+    #[test]
+    fn a_failing_step_short_circuits_every_later_step_in_the_same_pipeline() {
+        let pipelines = group_pipelines(&conversation(vec![
+            command_entry("cargo build", Some(1)),
+            command_entry("cargo test", Some(0)),
+            command_entry("cargo clippy", Some(0)),
+        ]));
+
+        assert_eq!(pipelines.len(), 1);
+        let steps = &pipelines[0].steps;
+        assert!(!steps[0].skipped, "the failing step itself still ran");
+        assert!(steps[1].skipped);
+        assert!(steps[2].skipped);
+    }
+
+    #[test]
+    fn a_step_with_no_result_yet_does_not_short_circuit() {
+        // A command that's still running (no `result` yet) hasn't failed, so
+        // later steps shouldn't be marked `skipped` just because of it.
+        let pipelines = group_pipelines(&conversation(vec![
+            command_entry("cargo build", None),
+            command_entry("cargo test", Some(0)),
+        ]));
+
+        assert!(pipelines[0].steps.iter().all(|step| !step.skipped));
+    }
+
+    #[test]
+    fn name_is_left_none_for_callers_to_fill_in() {
+        let pipelines = group_pipelines(&conversation(vec![command_entry("cargo build", Some(0))]));
+        assert_eq!(pipelines[0].name, None);
+    }
+
+    #[test]
+    fn empty_conversation_produces_no_pipelines() {
+        assert!(group_pipelines(&conversation(vec![])).is_empty());
+    }
+}
@@ -0,0 +1,391 @@
+//! Conversion of [`FileChange`]s into LSP-compatible [`WorkspaceEdit`]s, so
+//! an editor integration can apply an agent's proposed changes through the
+//! standard `workspace/applyEdit` flow instead of re-parsing diffs.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use url::Url;
+
+use super::diff::{parse_unified_diff, DiffLine};
+use super::FileChange;
+
+/// Errors converting a [`FileChange`] into a [`WorkspaceEdit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceEditError {
+    InvalidPath(String),
+    UnparsableDiff(String),
+}
+
+impl std::fmt::Display for WorkspaceEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkspaceEditError::InvalidPath(path) => write!(f, "invalid file path: {path}"),
+            WorkspaceEditError::UnparsableDiff(reason) => {
+                write!(f, "unparsable unified diff: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceEditError {}
+
+/// A 0-based line/character position, matching the LSP `Position` shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// A half-open `[start, end)` span, matching the LSP `Range` shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A single text replacement within a document, matching LSP `TextEdit`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+/// A set of `TextEdit`s to apply to one open document, matching LSP
+/// `TextDocumentEdit`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TextDocumentEdit {
+    pub uri: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A resource-level operation, as opposed to an edit of a document's
+/// content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export)]
+pub enum ResourceOp {
+    CreateFile {
+        uri: String,
+        overwrite: bool,
+        ignore_if_exists: bool,
+    },
+    DeleteFile {
+        uri: String,
+    },
+    RenameFile {
+        old_uri: String,
+        new_uri: String,
+    },
+}
+
+/// One entry of [`WorkspaceEdit::document_changes`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export)]
+pub enum DocumentChangeOperation {
+    Edit(TextDocumentEdit),
+    ResourceOp(ResourceOp),
+}
+
+/// An LSP-compatible `WorkspaceEdit`, suitable for a `workspace/applyEdit`
+/// request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WorkspaceEdit {
+    pub document_changes: Vec<DocumentChangeOperation>,
+}
+
+/// Converts the ordered `FileChange`s captured for a single `path` by
+/// `ActionType::FileEdit` into an LSP `WorkspaceEdit`.
+///
+/// `path` (and any `FileChange::Rename::new_path`) is usually repo-relative,
+/// as reported by an agent — `workspace_root` anchors it into the absolute
+/// path a `file://` URI requires. An already-absolute `path` is used as-is.
+///
+/// `original_source` is the file's content before any of `changes` are
+/// applied, when available. It's required to reconstruct line numbers for a
+/// `FileChange::Edit` whose `has_line_numbers` is `false`; without it, any
+/// hunk of such a diff that has a context line fails with
+/// `WorkspaceEditError::UnparsableDiff`, since there is nothing to locate
+/// its anchor in.
+pub fn file_edit_to_workspace_edit(
+    workspace_root: &Path,
+    path: &str,
+    changes: &[FileChange],
+    original_source: Option<&str>,
+) -> Result<WorkspaceEdit, WorkspaceEditError> {
+    // `FileChange::Rename` retargets every change that follows it, so the
+    // URI in use is tracked rather than computed once from `path`.
+    let mut current_uri = path_to_file_uri(workspace_root, path)?;
+    let mut document_changes = Vec::with_capacity(changes.len());
+
+    for change in changes {
+        match change {
+            FileChange::Write { content } => {
+                document_changes.push(DocumentChangeOperation::ResourceOp(
+                    ResourceOp::CreateFile {
+                        uri: current_uri.clone(),
+                        overwrite: true,
+                        ignore_if_exists: false,
+                    },
+                ));
+                document_changes.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+                    uri: current_uri.clone(),
+                    edits: vec![full_document_replace(content)],
+                }));
+            }
+            FileChange::Delete => {
+                document_changes.push(DocumentChangeOperation::ResourceOp(
+                    ResourceOp::DeleteFile {
+                        uri: current_uri.clone(),
+                    },
+                ));
+            }
+            FileChange::Rename { new_path } => {
+                let new_uri = path_to_file_uri(workspace_root, new_path)?;
+                document_changes.push(DocumentChangeOperation::ResourceOp(
+                    ResourceOp::RenameFile {
+                        old_uri: current_uri.clone(),
+                        new_uri: new_uri.clone(),
+                    },
+                ));
+                current_uri = new_uri;
+            }
+            FileChange::Edit {
+                unified_diff,
+                has_line_numbers,
+            } => {
+                document_changes.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+                    uri: current_uri.clone(),
+                    edits: diff_to_text_edits(unified_diff, *has_line_numbers, original_source)?,
+                }));
+            }
+        }
+    }
+
+    Ok(WorkspaceEdit { document_changes })
+}
+
+/// Converts a file path into a `file://` URI, joining it onto
+/// `workspace_root` first unless it's already absolute.
+fn path_to_file_uri(workspace_root: &Path, path: &str) -> Result<String, WorkspaceEditError> {
+    let path = Path::new(path);
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        workspace_root.join(path)
+    };
+    Url::from_file_path(&absolute)
+        .map(|url| url.to_string())
+        .map_err(|()| WorkspaceEditError::InvalidPath(absolute.display().to_string()))
+}
+
+fn full_document_replace(content: &str) -> TextEdit {
+    TextEdit {
+        range: Range {
+            start: Position {
+                line: 0,
+                character: 0,
+            },
+            end: Position {
+                line: u32::MAX,
+                character: 0,
+            },
+        },
+        new_text: content.to_string(),
+    }
+}
+
+/// Turns the hunks of a unified diff into `TextEdit`s via [`parse_unified_diff`].
+/// This is intentionally line-oriented rather than character-precise: each
+/// hunk becomes one edit that replaces its old line range with its new
+/// content. `original_source`, when given, lets `parse_unified_diff`
+/// reconstruct line numbers for hunks whose `has_line_numbers` is `false`.
+fn diff_to_text_edits(
+    unified_diff: &str,
+    has_line_numbers: bool,
+    original_source: Option<&str>,
+) -> Result<Vec<TextEdit>, WorkspaceEditError> {
+    let parsed = parse_unified_diff(unified_diff, has_line_numbers, original_source)
+        .map_err(|err| WorkspaceEditError::UnparsableDiff(err.to_string()))?;
+
+    Ok(parsed
+        .hunks
+        .into_iter()
+        .map(|hunk| {
+            let mut new_text = String::new();
+            for line in &hunk.lines {
+                match line {
+                    DiffLine::Added(text) | DiffLine::Context(text) => {
+                        new_text.push_str(text);
+                        new_text.push('\n');
+                    }
+                    DiffLine::Removed(_) => {}
+                }
+            }
+            // A pure insertion (`old_count == 0`) doesn't replace any old
+            // line, so its header's `old_start` already names the 0-based
+            // line to insert *before* rather than the 1-based line *after*
+            // which it inserts — subtracting 1 here would shift the new
+            // text one line too early.
+            let start_line = if hunk.old_count == 0 {
+                hunk.old_start
+            } else {
+                hunk.old_start.saturating_sub(1)
+            };
+
+            TextEdit {
+                range: Range {
+                    start: Position {
+                        line: start_line,
+                        character: 0,
+                    },
+                    end: Position {
+                        line: start_line + hunk.old_count,
+                        character: 0,
+                    },
+                },
+                new_text,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root() -> &'static Path {
+        Path::new("/workspace")
+    }
+
+    #[test]
+    fn write_emits_a_create_file_and_a_full_document_replace() {
+        let edit = file_edit_to_workspace_edit(
+            root(),
+            "src/main.rs",
+            &[FileChange::Write {
+                content: "fn main() {}\n".to_string(),
+            }],
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(edit.document_changes.len(), 2);
+        assert!(matches!(
+            edit.document_changes[0],
+            DocumentChangeOperation::ResourceOp(ResourceOp::CreateFile { overwrite: true, .. })
+        ));
+        let DocumentChangeOperation::Edit(doc_edit) = &edit.document_changes[1] else {
+            panic!("expected an Edit operation")
+        };
+        assert_eq!(doc_edit.edits[0].new_text, "fn main() {}\n");
+    }
+
+    #[test]
+    fn delete_emits_a_delete_file_resource_op() {
+        let edit =
+            file_edit_to_workspace_edit(root(), "src/main.rs", &[FileChange::Delete], None)
+                .unwrap();
+
+        assert_eq!(edit.document_changes.len(), 1);
+        assert!(matches!(
+            edit.document_changes[0],
+            DocumentChangeOperation::ResourceOp(ResourceOp::DeleteFile { .. })
+        ));
+    }
+
+    #[test]
+    fn rename_retargets_every_later_change() {
+        let edit = file_edit_to_workspace_edit(
+            root(),
+            "src/old.rs",
+            &[
+                FileChange::Rename {
+                    new_path: "src/new.rs".to_string(),
+                },
+                FileChange::Write {
+                    content: "".to_string(),
+                },
+            ],
+            None,
+        )
+        .unwrap();
+
+        let DocumentChangeOperation::ResourceOp(ResourceOp::RenameFile { old_uri, new_uri }) =
+            &edit.document_changes[0]
+        else {
+            panic!("expected a RenameFile operation")
+        };
+        assert!(old_uri.ends_with("src/old.rs"));
+        assert!(new_uri.ends_with("src/new.rs"));
+
+        // The Write that follows the rename must target the new URI, not the
+        // original one `path` referred to.
+        let DocumentChangeOperation::ResourceOp(ResourceOp::CreateFile { uri, .. }) =
+            &edit.document_changes[1]
+        else {
+            panic!("expected a CreateFile operation")
+        };
+        assert_eq!(uri, new_uri);
+    }
+
+    #[test]
+    fn relative_paths_are_joined_onto_the_workspace_root() {
+        let edit =
+            file_edit_to_workspace_edit(root(), "src/main.rs", &[FileChange::Delete], None)
+                .unwrap();
+        let DocumentChangeOperation::ResourceOp(ResourceOp::DeleteFile { uri }) =
+            &edit.document_changes[0]
+        else {
+            panic!("expected a DeleteFile operation")
+        };
+        assert_eq!(uri, "file:///workspace/src/main.rs");
+    }
+
+    #[test]
+    fn absolute_paths_are_used_as_is() {
+        let edit = file_edit_to_workspace_edit(
+            root(),
+            "/etc/hosts",
+            &[FileChange::Delete],
+            None,
+        )
+        .unwrap();
+        let DocumentChangeOperation::ResourceOp(ResourceOp::DeleteFile { uri }) =
+            &edit.document_changes[0]
+        else {
+            panic!("expected a DeleteFile operation")
+        };
+        assert_eq!(uri, "file:///etc/hosts");
+    }
+
+    #[test]
+    fn insertion_only_hunk_anchors_before_old_start_not_old_start_minus_one() {
+        // `old_count: 0` means nothing is replaced; the new text must land
+        // immediately before the 0-based line named by `old_start`, not one
+        // line earlier.
+        let diff = "@@ -2,0 +3,1 @@\n+inserted\n";
+        let edits = diff_to_text_edits(diff, true, None).unwrap();
+
+        assert_eq!(edits[0].range.start.line, 2);
+        assert_eq!(edits[0].range.end.line, 2);
+        assert_eq!(edits[0].new_text, "inserted\n");
+    }
+
+    #[test]
+    fn replacing_hunk_still_anchors_at_old_start_minus_one() {
+        let diff = "@@ -2,1 +2,1 @@\n-old\n+new\n";
+        let edits = diff_to_text_edits(diff, true, None).unwrap();
+
+        assert_eq!(edits[0].range.start.line, 1);
+        assert_eq!(edits[0].range.end.line, 2);
+        assert_eq!(edits[0].new_text, "new\n");
+    }
+}
@@ -1,5 +1,13 @@
 //! Cross-platform shell command utilities
 
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Child, Command as ProcessCommand, Stdio};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
 /// Returns the appropriate shell command and argument for the current platform.
 ///
 /// Returns (shell_program, shell_arg) where:
@@ -26,3 +34,840 @@ pub fn resolve_executable_path(executable: &str) -> Option<String> {
         .ok()
         .map(|p| p.to_string_lossy().to_string())
 }
+
+// --- Structured shell grammar -----------------------------------------
+//
+// `get_shell_command` hands a raw string to a platform shell, which means
+// the same command can behave differently depending on whether `sh`,
+// `bash`, or `cmd` interprets it. The types and functions below parse a
+// command line into a deterministic AST and evaluate it directly against
+// `std::process::Command`, in the spirit of deno_task_shell, so a
+// `CommandRun` action behaves identically on every platform.
+
+/// Errors produced while parsing a shell command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellParseError {
+    UnterminatedQuote,
+    UnexpectedToken(String),
+    EmptyCommand,
+}
+
+impl std::fmt::Display for ShellParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShellParseError::UnterminatedQuote => write!(f, "unterminated quote"),
+            ShellParseError::UnexpectedToken(token) => write!(f, "unexpected token: {token}"),
+            ShellParseError::EmptyCommand => write!(f, "empty command"),
+        }
+    }
+}
+
+impl std::error::Error for ShellParseError {}
+
+/// One literal or variable-reference fragment of a [`Word`], kept apart so
+/// expansion can happen at evaluation time (see [`expand_word`]) instead of
+/// being baked in while parsing: `export` only mutates `Evaluator::env` once
+/// the AST actually runs, so a static pre-expansion would miss it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+#[ts(export)]
+pub enum WordPart {
+    /// Literal text, including anything from a single-quoted section, which
+    /// suppresses expansion entirely.
+    Literal(String),
+    /// An unquoted or double-quoted `$VAR`/`${VAR}` reference.
+    Var(String),
+}
+
+/// A shell word after quote removal, as a sequence of literal/variable
+/// parts. Resolve it against an environment with [`expand_word`].
+pub type Word = Vec<WordPart>;
+
+/// Resolves every [`WordPart::Var`] in `word` against `env`, concatenating
+/// the result into a single string. Unknown variables expand to an empty
+/// string, matching POSIX shell semantics.
+pub fn expand_word(word: &[WordPart], env: &HashMap<String, String>) -> String {
+    word.iter()
+        .map(|part| match part {
+            WordPart::Literal(text) => text.clone(),
+            WordPart::Var(name) => env.get(name).cloned().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// A single redirection attached to a [`SimpleCommand`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", content = "target", rename_all = "snake_case")]
+#[ts(export)]
+pub enum Redirect {
+    /// `> target` — truncate and write.
+    Out(Word),
+    /// `>> target` — append.
+    Append(Word),
+    /// `< target` — read stdin from.
+    In(Word),
+}
+
+/// A command with no further control flow: a program, its arguments, any
+/// redirections, and any leading `VAR=value` assignments.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SimpleCommand {
+    pub program: Word,
+    pub args: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+    pub assignments: Vec<(String, Word)>,
+}
+
+/// The structured AST produced by [`parse`], so a caller like
+/// `ActionType::CommandRun` can render each stage of a pipeline/sequence
+/// instead of treating the command as an opaque string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+#[ts(export)]
+pub enum Command {
+    /// `a ; b ; c` — each runs regardless of the previous one's exit code.
+    Sequence(Vec<Command>),
+    /// `a && b` — `b` only runs if `a` exits successfully.
+    And(Box<Command>, Box<Command>),
+    /// `a || b` — `b` only runs if `a` exits unsuccessfully.
+    Or(Box<Command>, Box<Command>),
+    /// `a | b | c` — stdout of each stage feeds stdin of the next.
+    Pipeline(Vec<SimpleCommand>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(Word),
+    Semicolon,
+    AndAnd,
+    OrOr,
+    Pipe,
+    Gt,
+    GtGt,
+    Lt,
+}
+
+/// Parses a full command line into a [`Command`] AST. `$VAR`/`${VAR}`
+/// references are kept as [`WordPart::Var`] rather than expanded here;
+/// resolve them against an `Evaluator`'s environment at run time with
+/// [`expand_word`].
+pub fn parse(input: &str) -> Result<Command, ShellParseError> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Err(ShellParseError::EmptyCommand);
+    }
+
+    let mut commands = Vec::new();
+    for part in tokens.split(|t| *t == Token::Semicolon) {
+        if part.is_empty() {
+            continue;
+        }
+        commands.push(parse_and_or(part)?);
+    }
+
+    if commands.is_empty() {
+        return Err(ShellParseError::EmptyCommand);
+    }
+    Ok(if commands.len() == 1 {
+        commands.into_iter().next().unwrap()
+    } else {
+        Command::Sequence(commands)
+    })
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ShellParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                chars.next();
+            }
+            ';' => {
+                chars.next();
+                tokens.push(Token::Semicolon);
+            }
+            '&' => {
+                chars.next();
+                if chars.peek() == Some(&'&') {
+                    chars.next();
+                    tokens.push(Token::AndAnd);
+                } else {
+                    return Err(ShellParseError::UnexpectedToken("&".to_string()));
+                }
+            }
+            '|' => {
+                chars.next();
+                if chars.peek() == Some(&'|') {
+                    chars.next();
+                    tokens.push(Token::OrOr);
+                } else {
+                    tokens.push(Token::Pipe);
+                }
+            }
+            '>' => {
+                chars.next();
+                if chars.peek() == Some(&'>') {
+                    chars.next();
+                    tokens.push(Token::GtGt);
+                } else {
+                    tokens.push(Token::Gt);
+                }
+            }
+            '<' => {
+                chars.next();
+                tokens.push(Token::Lt);
+            }
+            _ => tokens.push(Token::Word(read_word(&mut chars)?)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn read_word(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Word, ShellParseError> {
+    let mut parts: Word = Vec::new();
+    let mut literal = String::new();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' | ';' | '&' | '|' | '>' | '<' => break,
+            '\'' => {
+                // Single quotes suppress all expansion, so their contents
+                // are copied straight into the literal buffer.
+                chars.next();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        closed = true;
+                        break;
+                    }
+                    literal.push(c);
+                }
+                if !closed {
+                    return Err(ShellParseError::UnterminatedQuote);
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut raw = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        closed = true;
+                        break;
+                    }
+                    raw.push(c);
+                }
+                if !closed {
+                    return Err(ShellParseError::UnterminatedQuote);
+                }
+                push_expandable(&raw, &mut parts, &mut literal);
+            }
+            '$' => {
+                chars.next();
+                if !literal.is_empty() {
+                    parts.push(WordPart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(WordPart::Var(read_var_name(chars)));
+            }
+            _ => {
+                chars.next();
+                literal.push(c);
+            }
+        }
+    }
+
+    if !literal.is_empty() {
+        parts.push(WordPart::Literal(literal));
+    }
+    Ok(parts)
+}
+
+/// Scans double-quoted `text` for `$VAR`/`${VAR}` references, appending
+/// literal runs and variable references as they're found. `literal` is
+/// flushed into `parts` before each variable reference and at the end by
+/// the caller.
+fn push_expandable(text: &str, parts: &mut Word, literal: &mut String) {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' {
+            if !literal.is_empty() {
+                parts.push(WordPart::Literal(std::mem::take(literal)));
+            }
+            parts.push(WordPart::Var(read_var_name(&mut chars)));
+        } else {
+            literal.push(c);
+        }
+    }
+}
+
+fn read_var_name(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    if chars.peek() == Some(&'{') {
+        chars.next();
+        let mut name = String::new();
+        for c in chars.by_ref() {
+            if c == '}' {
+                break;
+            }
+            name.push(c);
+        }
+        name
+    } else {
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                name.push(c);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        name
+    }
+}
+
+fn parse_and_or(tokens: &[Token]) -> Result<Command, ShellParseError> {
+    let mut segments: Vec<&[Token]> = Vec::new();
+    let mut operators: Vec<bool> = Vec::new(); // true => &&, false => ||
+    let mut start = 0;
+
+    for (i, tok) in tokens.iter().enumerate() {
+        match tok {
+            Token::AndAnd => {
+                segments.push(&tokens[start..i]);
+                operators.push(true);
+                start = i + 1;
+            }
+            Token::OrOr => {
+                segments.push(&tokens[start..i]);
+                operators.push(false);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&tokens[start..]);
+
+    let mut segments = segments.into_iter();
+    let mut command = parse_pipeline(segments.next().ok_or(ShellParseError::EmptyCommand)?)?;
+    for (segment, is_and) in segments.zip(operators) {
+        let rhs = parse_pipeline(segment)?;
+        command = if is_and {
+            Command::And(Box::new(command), Box::new(rhs))
+        } else {
+            Command::Or(Box::new(command), Box::new(rhs))
+        };
+    }
+    Ok(command)
+}
+
+fn parse_pipeline(tokens: &[Token]) -> Result<Command, ShellParseError> {
+    let mut stages = Vec::new();
+    let mut start = 0;
+    for (i, tok) in tokens.iter().enumerate() {
+        if *tok == Token::Pipe {
+            stages.push(parse_simple_command(&tokens[start..i])?);
+            start = i + 1;
+        }
+    }
+    stages.push(parse_simple_command(&tokens[start..])?);
+    Ok(Command::Pipeline(stages))
+}
+
+fn parse_simple_command(tokens: &[Token]) -> Result<SimpleCommand, ShellParseError> {
+    let mut simple = SimpleCommand::default();
+    let mut iter = tokens.iter();
+    let mut seen_program = false;
+
+    while let Some(tok) = iter.next() {
+        match tok {
+            Token::Word(word) => {
+                if !seen_program {
+                    if let Some((name, value)) = split_assignment(word) {
+                        simple.assignments.push((name, value));
+                        continue;
+                    }
+                    simple.program = word.clone();
+                    seen_program = true;
+                } else {
+                    simple.args.push(word.clone());
+                }
+            }
+            Token::Gt | Token::GtGt | Token::Lt => {
+                let target = match iter.next() {
+                    Some(Token::Word(word)) => word.clone(),
+                    _ => {
+                        return Err(ShellParseError::UnexpectedToken(
+                            "redirection target".to_string(),
+                        ))
+                    }
+                };
+                simple.redirects.push(match tok {
+                    Token::Gt => Redirect::Out(target),
+                    Token::GtGt => Redirect::Append(target),
+                    Token::Lt => Redirect::In(target),
+                    _ => unreachable!(),
+                });
+            }
+            other => return Err(ShellParseError::UnexpectedToken(format!("{other:?}"))),
+        }
+    }
+
+    if simple.program.is_empty() {
+        return Err(ShellParseError::EmptyCommand);
+    }
+    Ok(simple)
+}
+
+/// Splits `word` into a `NAME=value` assignment if its leading literal text
+/// starts with a valid assignment name followed by `=`. The name must be
+/// fully literal (assignment names can't themselves be the result of
+/// expansion), but the value may contain `$VAR` references, e.g. `FOO=$BAR`.
+fn split_assignment(word: &Word) -> Option<(String, Word)> {
+    let (first, rest) = word.split_first()?;
+    let WordPart::Literal(text) = first else {
+        return None;
+    };
+    let (name, first_value) = text.split_once('=')?;
+    if !is_valid_assignment_name(name) {
+        return None;
+    }
+
+    let mut value = Vec::with_capacity(1 + rest.len());
+    if !first_value.is_empty() {
+        value.push(WordPart::Literal(first_value.to_string()));
+    }
+    value.extend(rest.iter().cloned());
+    Some((name.to_string(), value))
+}
+
+fn is_valid_assignment_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Executes a parsed [`Command`] tree directly via `std::process::Command`,
+/// never handing the raw string to a platform shell. Tracks a working
+/// directory and environment map so that `cd`/`export` builtins, which
+/// mutate evaluator state rather than spawning a process, behave as
+/// expected across subsequent commands, and so that `$VAR` references are
+/// resolved against the environment as it stands when each word actually
+/// runs rather than when the command line was parsed.
+pub struct Evaluator {
+    pub env: HashMap<String, String>,
+    pub cwd: PathBuf,
+}
+
+impl Evaluator {
+    pub fn new(env: HashMap<String, String>, cwd: PathBuf) -> Self {
+        Self { env, cwd }
+    }
+
+    /// Runs `command`, returning the exit code to report to the caller.
+    /// `&&`/`||` short-circuit as documented on [`Command`]; a `Sequence`
+    /// reports the exit code of its last member.
+    pub fn run(&mut self, command: &Command) -> io::Result<i32> {
+        match command {
+            Command::Sequence(commands) => {
+                let mut status = 0;
+                for command in commands {
+                    status = self.run(command)?;
+                }
+                Ok(status)
+            }
+            Command::And(lhs, rhs) => {
+                let status = self.run(lhs)?;
+                if status != 0 {
+                    Ok(status)
+                } else {
+                    self.run(rhs)
+                }
+            }
+            Command::Or(lhs, rhs) => {
+                let status = self.run(lhs)?;
+                if status == 0 {
+                    Ok(status)
+                } else {
+                    self.run(rhs)
+                }
+            }
+            Command::Pipeline(stages) => self.run_pipeline(stages),
+        }
+    }
+
+    fn run_pipeline(&mut self, stages: &[SimpleCommand]) -> io::Result<i32> {
+        if stages.len() == 1 {
+            return self.run_simple(&stages[0]);
+        }
+
+        let mut children: Vec<Child> = Vec::with_capacity(stages.len());
+        let mut previous_stdout: Option<std::process::ChildStdout> = None;
+
+        for (i, stage) in stages.iter().enumerate() {
+            if let Some(builtin_status) = self.try_builtin(stage) {
+                previous_stdout = None;
+                if i == stages.len() - 1 {
+                    return builtin_status;
+                }
+                continue;
+            }
+
+            let program = expand_word(&stage.program, &self.env);
+            let Some(program_path) = resolve_executable_path(&program) else {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("command not found: {program}"),
+                ));
+            };
+            let args: Vec<String> = stage
+                .args
+                .iter()
+                .map(|word| expand_word(word, &self.env))
+                .collect();
+
+            let mut command = ProcessCommand::new(program_path);
+            command
+                .args(&args)
+                .current_dir(&self.cwd)
+                .envs(&self.env)
+                .stdin(previous_stdout.take().map_or(Stdio::inherit(), Stdio::from))
+                .stdout(if i == stages.len() - 1 {
+                    Stdio::inherit()
+                } else {
+                    Stdio::piped()
+                });
+            // A stage's own redirects (e.g. `cmd > out.txt | next`) take
+            // precedence over the pipe wiring set up above.
+            self.apply_redirects(&mut command, &stage.redirects)?;
+
+            let mut child = command.spawn()?;
+            previous_stdout = child.stdout.take();
+            children.push(child);
+        }
+
+        // The pipeline's exit code is that of its last stage.
+        let mut last_status = 0;
+        for mut child in children {
+            last_status = child.wait()?.code().unwrap_or(-1);
+        }
+        Ok(last_status)
+    }
+
+    fn run_simple(&mut self, simple: &SimpleCommand) -> io::Result<i32> {
+        if let Some(status) = self.try_builtin(simple) {
+            return status;
+        }
+
+        let program = expand_word(&simple.program, &self.env);
+        let Some(program_path) = resolve_executable_path(&program) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("command not found: {program}"),
+            ));
+        };
+        let args: Vec<String> = simple
+            .args
+            .iter()
+            .map(|word| expand_word(word, &self.env))
+            .collect();
+
+        let mut command = ProcessCommand::new(program_path);
+        command.args(&args).current_dir(&self.cwd).envs(&self.env);
+        self.apply_redirects(&mut command, &simple.redirects)?;
+
+        Ok(command.spawn()?.wait()?.code().unwrap_or(-1))
+    }
+
+    /// Handles `cd`/`export`, which mutate evaluator state rather than
+    /// spawning a process. Returns `None` when `simple` is not a builtin.
+    fn try_builtin(&mut self, simple: &SimpleCommand) -> Option<io::Result<i32>> {
+        match expand_word(&simple.program, &self.env).as_str() {
+            "cd" => {
+                let target = simple
+                    .args
+                    .first()
+                    .map(|word| expand_word(word, &self.env))
+                    .or_else(|| self.env.get("HOME").cloned())
+                    .unwrap_or_default();
+                let new_dir = self.cwd.join(target);
+                Some(match new_dir.canonicalize() {
+                    Ok(path) => {
+                        self.cwd = path;
+                        Ok(0)
+                    }
+                    Err(err) => Err(err),
+                })
+            }
+            "export" => {
+                for (name, value) in &simple.assignments {
+                    let value = expand_word(value, &self.env);
+                    self.env.insert(name.clone(), value);
+                }
+                for arg in &simple.args {
+                    if let Some((name, value)) = split_assignment(arg) {
+                        let value = expand_word(&value, &self.env);
+                        self.env.insert(name, value);
+                    }
+                }
+                Some(Ok(0))
+            }
+            _ => None,
+        }
+    }
+
+    fn apply_redirects(
+        &self,
+        command: &mut ProcessCommand,
+        redirects: &[Redirect],
+    ) -> io::Result<()> {
+        for redirect in redirects {
+            match redirect {
+                Redirect::Out(word) => {
+                    command.stdout(std::fs::File::create(expand_word(word, &self.env))?);
+                }
+                Redirect::Append(word) => {
+                    command.stdout(
+                        std::fs::OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(expand_word(word, &self.env))?,
+                    );
+                }
+                Redirect::In(word) => {
+                    command.stdin(std::fs::File::open(expand_word(word, &self.env))?);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn literal_word(s: &str) -> Word {
+        vec![WordPart::Literal(s.to_string())]
+    }
+
+    #[test]
+    fn parses_simple_command_with_args() {
+        let command = parse("echo hello world").unwrap();
+        assert_eq!(
+            command,
+            Command::Pipeline(vec![SimpleCommand {
+                program: literal_word("echo"),
+                args: vec![literal_word("hello"), literal_word("world")],
+                ..Default::default()
+            }])
+        );
+    }
+
+    #[test]
+    fn single_quotes_suppress_expansion() {
+        let command = parse("echo '$HOME'").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(stages[0].args, vec![literal_word("$HOME")]);
+        assert_eq!(
+            expand_word(&stages[0].args[0], &env(&[("HOME", "/root")])),
+            "$HOME",
+            "single quotes must suppress expansion even when the var exists"
+        );
+    }
+
+    #[test]
+    fn double_quotes_expand_env_vars() {
+        let command = parse("echo \"$HOME/bin\"").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(
+            stages[0].args,
+            vec![vec![
+                WordPart::Var("HOME".to_string()),
+                WordPart::Literal("/bin".to_string()),
+            ]]
+        );
+        assert_eq!(
+            expand_word(&stages[0].args[0], &env(&[("HOME", "/root")])),
+            "/root/bin"
+        );
+    }
+
+    #[test]
+    fn braced_and_unknown_vars_expand() {
+        let command = parse("echo ${HOME}-${MISSING}").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(
+            expand_word(&stages[0].args[0], &env(&[("HOME", "/root")])),
+            "/root-"
+        );
+    }
+
+    #[test]
+    fn variable_references_resolve_against_evaluator_env_not_parse_time_env() {
+        // `$FOO` must resolve against whatever `export` has set by the time
+        // this word is evaluated, not a snapshot taken when the line was
+        // parsed — `parse` no longer even takes an environment.
+        let command = parse("echo $FOO").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(stages[0].args, vec![vec![WordPart::Var("FOO".to_string())]]);
+
+        let mut evaluator = Evaluator::new(env(&[]), std::env::temp_dir());
+        evaluator.env.insert("FOO".to_string(), "bar".to_string());
+        assert_eq!(expand_word(&stages[0].args[0], &evaluator.env), "bar");
+    }
+
+    #[test]
+    fn semicolons_produce_a_sequence() {
+        let command = parse("echo a ; echo b").unwrap();
+        match command {
+            Command::Sequence(commands) => assert_eq!(commands.len(), 2),
+            other => panic!("expected Sequence, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn and_or_produce_nested_commands() {
+        let command = parse("a && b || c").unwrap();
+        match command {
+            Command::Or(lhs, _) => assert!(matches!(*lhs, Command::And(_, _))),
+            other => panic!("expected Or(And(..), ..), got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn pipe_produces_pipeline_of_simple_commands() {
+        let command = parse("ps aux | grep cargo").unwrap();
+        match command {
+            Command::Pipeline(stages) => {
+                assert_eq!(stages.len(), 2);
+                assert_eq!(stages[0].program, literal_word("ps"));
+                assert_eq!(stages[1].program, literal_word("grep"));
+            }
+            other => panic!("expected Pipeline, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn redirects_are_parsed_onto_the_simple_command() {
+        let command = parse("cmd > out.txt").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(stages[0].redirects, vec![Redirect::Out(literal_word("out.txt"))]);
+
+        let command = parse("cmd >> out.txt").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(
+            stages[0].redirects,
+            vec![Redirect::Append(literal_word("out.txt"))]
+        );
+
+        let command = parse("cmd < in.txt").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(stages[0].redirects, vec![Redirect::In(literal_word("in.txt"))]);
+    }
+
+    #[test]
+    fn leading_assignment_is_not_treated_as_the_program() {
+        let command = parse("FOO=bar echo hi").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(
+            stages[0].assignments,
+            vec![("FOO".to_string(), literal_word("bar"))]
+        );
+        assert_eq!(stages[0].program, literal_word("echo"));
+    }
+
+    #[test]
+    fn assignment_value_may_itself_reference_a_variable() {
+        let command = parse("FOO=$BAR echo hi").unwrap();
+        let Command::Pipeline(stages) = command else {
+            panic!("expected a pipeline")
+        };
+        assert_eq!(
+            stages[0].assignments,
+            vec![("FOO".to_string(), vec![WordPart::Var("BAR".to_string())])]
+        );
+    }
+
+    #[test]
+    fn empty_command_is_an_error() {
+        assert_eq!(parse(""), Err(ShellParseError::EmptyCommand));
+        assert_eq!(parse("   "), Err(ShellParseError::EmptyCommand));
+    }
+
+    #[test]
+    fn unterminated_quote_is_an_error() {
+        assert_eq!(
+            parse("echo 'unterminated"),
+            Err(ShellParseError::UnterminatedQuote)
+        );
+    }
+
+    #[test]
+    fn and_short_circuits_and_propagates_first_failure() {
+        let command = parse("false && true").unwrap();
+        let mut evaluator = Evaluator::new(env(&[]), std::env::temp_dir());
+        let status = evaluator.run(&command).unwrap();
+        assert_eq!(status, 1, "should propagate false's exit code, not run true");
+    }
+
+    #[test]
+    fn or_short_circuits_on_first_success() {
+        let command = parse("true || false").unwrap();
+        let mut evaluator = Evaluator::new(env(&[]), std::env::temp_dir());
+        let status = evaluator.run(&command).unwrap();
+        assert_eq!(status, 0);
+    }
+
+    #[test]
+    fn pipeline_exit_code_is_the_last_stage_not_the_first() {
+        let command = parse("false | true").unwrap();
+        let mut evaluator = Evaluator::new(env(&[]), std::env::temp_dir());
+        let status = evaluator.run(&command).unwrap();
+        assert_eq!(status, 0, "pipeline status follows the last stage");
+    }
+
+    #[test]
+    fn cd_builtin_mutates_evaluator_cwd() {
+        let command = parse("cd /").unwrap();
+        let mut evaluator = Evaluator::new(env(&[]), std::env::temp_dir());
+        let status = evaluator.run(&command).unwrap();
+        assert_eq!(status, 0);
+        assert_eq!(evaluator.cwd, PathBuf::from("/"));
+    }
+
+    #[test]
+    fn export_builtin_mutates_evaluator_env() {
+        let command = parse("export FOO=bar").unwrap();
+        let mut evaluator = Evaluator::new(env(&[]), std::env::temp_dir());
+        let status = evaluator.run(&command).unwrap();
+        assert_eq!(status, 0);
+        assert_eq!(evaluator.env.get("FOO"), Some(&"bar".to_string()));
+    }
+}